@@ -1,11 +1,68 @@
+mod metrics;
+mod runs;
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json};
+use axum::routing::{get, post};
+use axum::Router;
+use staterail_engine::EngineEvent;
+use tokio::sync::Mutex;
 use tracing_subscriber::FmtSubscriber;
+use uuid::Uuid;
+
+use metrics::Metrics;
+use runs::RunsView;
+
+/// This service has no engine of its own: it only knows what's been `POST`ed
+/// to `/ingest`. An engine process forwards its event stream here (see
+/// `ANALYTICS_URL` in `apps/engine`) so `/metrics` and `/workflows/:run_id`
+/// reflect whatever engine(s) are configured to report to it.
+#[derive(Clone)]
+struct AppState {
+    metrics: Arc<Mutex<Metrics>>,
+    runs: Arc<Mutex<RunsView>>,
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let subscriber = FmtSubscriber::builder().with_env_filter("info").finish();
     tracing::subscriber::set_global_default(subscriber)?;
 
-    tracing::info!("analytics service scaffold start");
-    // TODO: connect to event stream and expose metrics API
+    let state = AppState {
+        metrics: Arc::new(Mutex::new(Metrics::new())),
+        runs: Arc::new(Mutex::new(RunsView::new())),
+    };
+
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .route("/workflows/:run_id", get(workflow_handler))
+        .route("/ingest", post(ingest_handler))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:9090").await?;
+    tracing::info!(addr = %listener.local_addr()?, "analytics service listening");
+    axum::serve(listener, app).await?;
     Ok(())
 }
+
+async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    state.metrics.lock().await.render_prometheus()
+}
+
+async fn workflow_handler(State(state): State<AppState>, Path(run_id): Path<Uuid>) -> impl IntoResponse {
+    match state.runs.lock().await.get(run_id) {
+        Some(snapshot) => Json(snapshot).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Receives a single event forwarded by an engine process and folds it into
+/// both the Prometheus metrics and the per-run state view.
+async fn ingest_handler(State(state): State<AppState>, Json(event): Json<EngineEvent>) -> StatusCode {
+    state.metrics.lock().await.record(&event);
+    state.runs.lock().await.record(&event);
+    StatusCode::OK
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use staterail_engine::{EngineEvent, StepState, WorkflowEvent, WorkflowLifecycleEvent, WorkflowState};
+
+/// Observes an engine's event stream and maintains counters/histograms over
+/// it. `record` is plain and synchronous so it's testable independently of
+/// however events are delivered (broadcast stream, HTTP relay, ...).
+#[derive(Default)]
+pub struct Metrics {
+    workflows_started: u64,
+    workflows_completed: u64,
+    workflows_failed: u64,
+    step_executions: HashMap<String, u64>,
+    step_successes: HashMap<String, u64>,
+    step_failures: HashMap<String, u64>,
+    retries: HashMap<String, u64>,
+    step_durations_ms: HashMap<String, Vec<f64>>,
+    step_started_at: HashMap<(uuid::Uuid, String), DateTime<Utc>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, event: &EngineEvent) {
+        match event {
+            EngineEvent::Workflow(event) => self.record_workflow(event),
+            EngineEvent::Step(event) => self.record_step(event),
+        }
+    }
+
+    fn record_workflow(&mut self, event: &WorkflowLifecycleEvent) {
+        match event.to {
+            WorkflowState::Running if event.from == WorkflowState::Created => self.workflows_started += 1,
+            WorkflowState::Completed => self.workflows_completed += 1,
+            WorkflowState::Failed => self.workflows_failed += 1,
+            _ => {}
+        }
+    }
+
+    fn record_step(&mut self, event: &WorkflowEvent) {
+        let key = (event.run_id, event.step_id.clone());
+        match (&event.from, &event.to) {
+            (StepState::Queued, StepState::Running) => {
+                self.step_started_at.insert(key, event.timestamp);
+            }
+            (StepState::Running, StepState::Running) => {
+                *self.retries.entry(event.step_kind.clone()).or_default() += 1;
+            }
+            (StepState::Running, StepState::Succeeded) => {
+                *self.step_executions.entry(event.step_kind.clone()).or_default() += 1;
+                *self.step_successes.entry(event.step_kind.clone()).or_default() += 1;
+                self.record_duration(&key, event);
+            }
+            (StepState::Running, StepState::Failed) => {
+                *self.step_executions.entry(event.step_kind.clone()).or_default() += 1;
+                *self.step_failures.entry(event.step_kind.clone()).or_default() += 1;
+                self.record_duration(&key, event);
+            }
+            _ => {}
+        }
+    }
+
+    fn record_duration(&mut self, key: &(uuid::Uuid, String), event: &WorkflowEvent) {
+        if let Some(started_at) = self.step_started_at.remove(key) {
+            let duration_ms = (event.timestamp - started_at).num_milliseconds().max(0) as f64;
+            self.step_durations_ms.entry(event.step_kind.clone()).or_default().push(duration_ms);
+        }
+    }
+
+    /// Renders the collected metrics in Prometheus text exposition format.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP staterail_workflows_started_total Workflows started.\n");
+        out.push_str("# TYPE staterail_workflows_started_total counter\n");
+        out.push_str(&format!("staterail_workflows_started_total {}\n", self.workflows_started));
+
+        out.push_str("# HELP staterail_workflows_completed_total Workflows completed successfully.\n");
+        out.push_str("# TYPE staterail_workflows_completed_total counter\n");
+        out.push_str(&format!("staterail_workflows_completed_total {}\n", self.workflows_completed));
+
+        out.push_str("# HELP staterail_workflows_failed_total Workflows that ended in failure.\n");
+        out.push_str("# TYPE staterail_workflows_failed_total counter\n");
+        out.push_str(&format!("staterail_workflows_failed_total {}\n", self.workflows_failed));
+
+        out.push_str("# HELP staterail_step_executions_total Step executions, by step kind.\n");
+        out.push_str("# TYPE staterail_step_executions_total counter\n");
+        for (kind, count) in sorted(&self.step_executions) {
+            out.push_str(&format!("staterail_step_executions_total{{kind=\"{kind}\"}} {count}\n"));
+        }
+
+        out.push_str("# HELP staterail_step_successes_total Successful step executions, by step kind.\n");
+        out.push_str("# TYPE staterail_step_successes_total counter\n");
+        for (kind, count) in sorted(&self.step_successes) {
+            out.push_str(&format!("staterail_step_successes_total{{kind=\"{kind}\"}} {count}\n"));
+        }
+
+        out.push_str("# HELP staterail_step_failures_total Failed step executions, by step kind.\n");
+        out.push_str("# TYPE staterail_step_failures_total counter\n");
+        for (kind, count) in sorted(&self.step_failures) {
+            out.push_str(&format!("staterail_step_failures_total{{kind=\"{kind}\"}} {count}\n"));
+        }
+
+        out.push_str("# HELP staterail_step_retries_total Retry attempts, by step kind.\n");
+        out.push_str("# TYPE staterail_step_retries_total counter\n");
+        for (kind, count) in sorted(&self.retries) {
+            out.push_str(&format!("staterail_step_retries_total{{kind=\"{kind}\"}} {count}\n"));
+        }
+
+        out.push_str("# HELP staterail_step_duration_ms Step execution duration in milliseconds, by step kind.\n");
+        out.push_str("# TYPE staterail_step_duration_ms histogram\n");
+        for (kind, samples) in self.step_durations_ms.iter() {
+            render_histogram(&mut out, kind, samples);
+        }
+
+        out
+    }
+}
+
+fn sorted(counts: &HashMap<String, u64>) -> Vec<(&String, &u64)> {
+    let mut entries: Vec<_> = counts.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+const DURATION_BUCKETS_MS: &[f64] = &[10.0, 50.0, 100.0, 500.0, 1_000.0, 5_000.0, 30_000.0];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use uuid::Uuid;
+
+    fn step_event(run_id: Uuid, from: StepState, to: StepState, timestamp: DateTime<Utc>) -> EngineEvent {
+        EngineEvent::Step(WorkflowEvent {
+            run_id,
+            workflow_id: "wf".to_string(),
+            step_id: "a".to_string(),
+            step_kind: "http".to_string(),
+            from,
+            to,
+            attempt: 1,
+            timestamp,
+        })
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative_per_bucket_not_summed_across_buckets() {
+        let mut metrics = Metrics::new();
+        let run_id = Uuid::new_v4();
+        let started = Utc::now();
+
+        metrics.record(&step_event(run_id, StepState::Queued, StepState::Running, started));
+        metrics.record(&step_event(
+            run_id,
+            StepState::Running,
+            StepState::Succeeded,
+            started + ChronoDuration::milliseconds(5),
+        ));
+
+        let rendered = metrics.render_prometheus();
+        assert!(rendered.contains("staterail_step_duration_ms_bucket{kind=\"http\",le=\"10\"} 1"));
+        assert!(rendered.contains("staterail_step_duration_ms_bucket{kind=\"http\",le=\"30000\"} 1"));
+        assert!(rendered.contains("staterail_step_duration_ms_bucket{kind=\"http\",le=\"+Inf\"} 1"));
+        assert!(rendered.contains("staterail_step_duration_ms_count{kind=\"http\"} 1"));
+    }
+
+    #[test]
+    fn record_counts_executions_successes_and_retries() {
+        let mut metrics = Metrics::new();
+        let run_id = Uuid::new_v4();
+        let started = Utc::now();
+
+        metrics.record(&step_event(run_id, StepState::Queued, StepState::Running, started));
+        metrics.record(&step_event(run_id, StepState::Running, StepState::Running, started));
+        metrics.record(&step_event(
+            run_id,
+            StepState::Running,
+            StepState::Succeeded,
+            started + ChronoDuration::milliseconds(1),
+        ));
+
+        assert_eq!(metrics.step_executions.get("http"), Some(&1));
+        assert_eq!(metrics.step_successes.get("http"), Some(&1));
+        assert_eq!(metrics.retries.get("http"), Some(&1));
+    }
+
+    #[test]
+    fn record_workflow_lifecycle_counts() {
+        let mut metrics = Metrics::new();
+        let run_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        metrics.record(&EngineEvent::Workflow(WorkflowLifecycleEvent {
+            run_id,
+            workflow_id: "wf".to_string(),
+            from: WorkflowState::Created,
+            to: WorkflowState::Running,
+            timestamp: now,
+        }));
+        metrics.record(&EngineEvent::Workflow(WorkflowLifecycleEvent {
+            run_id,
+            workflow_id: "wf".to_string(),
+            from: WorkflowState::Running,
+            to: WorkflowState::Completed,
+            timestamp: now,
+        }));
+
+        assert_eq!(metrics.workflows_started, 1);
+        assert_eq!(metrics.workflows_completed, 1);
+    }
+}
+
+fn render_histogram(out: &mut String, kind: &str, samples: &[f64]) {
+    for bucket in DURATION_BUCKETS_MS {
+        let count = samples.iter().filter(|&&sample| sample <= *bucket).count() as u64;
+        out.push_str(&format!(
+            "staterail_step_duration_ms_bucket{{kind=\"{kind}\",le=\"{bucket}\"}} {count}\n"
+        ));
+    }
+    out.push_str(&format!(
+        "staterail_step_duration_ms_bucket{{kind=\"{kind}\",le=\"+Inf\"}} {}\n",
+        samples.len()
+    ));
+    let sum: f64 = samples.iter().sum();
+    out.push_str(&format!("staterail_step_duration_ms_sum{{kind=\"{kind}\"}} {sum}\n"));
+    out.push_str(&format!(
+        "staterail_step_duration_ms_count{{kind=\"{kind}\"}} {}\n",
+        samples.len()
+    ));
+}
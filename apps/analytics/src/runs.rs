@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use staterail_engine::{EngineEvent, StepState, WorkflowState};
+use uuid::Uuid;
+
+/// A run's state as reconstructed purely from the events this service has
+/// ingested, independent of whatever engine process produced them.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSnapshot {
+    pub run_id: Uuid,
+    pub workflow_id: String,
+    pub state: WorkflowState,
+    pub step_states: HashMap<String, StepState>,
+}
+
+/// Tracks the latest known state of every run seen on the ingested event
+/// stream. `record` is plain and synchronous, same as `Metrics::record`.
+#[derive(Default)]
+pub struct RunsView {
+    runs: HashMap<Uuid, RunSnapshot>,
+}
+
+impl RunsView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, event: &EngineEvent) {
+        let run_id = event.run_id();
+        let run = self.runs.entry(run_id).or_insert_with(|| RunSnapshot {
+            run_id,
+            workflow_id: event.workflow_id().to_string(),
+            state: WorkflowState::default(),
+            step_states: HashMap::new(),
+        });
+
+        match event {
+            EngineEvent::Workflow(event) => run.state = event.to.clone(),
+            EngineEvent::Step(event) => {
+                run.step_states.insert(event.step_id.clone(), event.to.clone());
+            }
+        }
+    }
+
+    pub fn get(&self, run_id: Uuid) -> Option<RunSnapshot> {
+        self.runs.get(&run_id).cloned()
+    }
+}
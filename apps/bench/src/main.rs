@@ -0,0 +1,265 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use staterail_engine::{Engine, EngineError, EngineEvent, StepExecutor, StepState, WorkflowDefinition, WorkflowNotification, WorkflowState};
+use tokio::sync::Mutex;
+use tokio_stream::StreamExt;
+use tracing_subscriber::FmtSubscriber;
+use uuid::Uuid;
+
+/// A named workload: a set of workflow definitions to drive concurrently,
+/// with optional simulated per-`kind` step latency so the benchmark
+/// measures engine scheduling overhead rather than real I/O.
+#[derive(Debug, Deserialize)]
+struct Workload {
+    name: String,
+    workflows: Vec<WorkflowDefinition>,
+    concurrent_runs: u32,
+    #[serde(default)]
+    step_latencies_ms: HashMap<String, u64>,
+}
+
+/// Sleeps for a configured duration and returns `null`, so a benchmark run
+/// measures the engine's own overhead instead of any real step I/O.
+struct NoopExecutor {
+    latency: Duration,
+}
+
+#[async_trait]
+impl StepExecutor for NoopExecutor {
+    async fn execute(&self, _config: &Value) -> Result<Value, EngineError> {
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+        Ok(Value::Null)
+    }
+}
+
+#[derive(Debug, Serialize, Default)]
+struct Percentiles {
+    p50_ms: f64,
+    p95_ms: f64,
+    p99_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct WorkloadReport {
+    name: String,
+    total_workflows: u64,
+    wall_time_ms: f64,
+    workflows_per_sec: f64,
+    step_latency: Percentiles,
+    end_to_end_latency: Percentiles,
+    retries: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let subscriber = FmtSubscriber::builder().with_env_filter("info").finish();
+    tracing::subscriber::set_global_default(subscriber)?;
+
+    let mut paths = Vec::new();
+    let mut post_url = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--post-url" {
+            post_url = Some(args.next().ok_or("--post-url requires a URL")?);
+        } else {
+            paths.push(arg);
+        }
+    }
+    if paths.is_empty() {
+        return Err("usage: bench <workload.json>... [--post-url URL]".into());
+    }
+
+    let mut reports = Vec::new();
+    for path in paths {
+        let contents = std::fs::read_to_string(&path)?;
+        let workload: Workload = serde_json::from_str(&contents)?;
+        tracing::info!(workload = %workload.name, path, "running workload");
+        reports.push(run_workload(workload).await?);
+    }
+
+    let json = serde_json::to_string_pretty(&reports)?;
+    println!("{json}");
+
+    if let Some(url) = post_url {
+        let client = reqwest::Client::new();
+        client.post(url).json(&reports).send().await?;
+    }
+
+    Ok(())
+}
+
+async fn run_workload(workload: Workload) -> Result<WorkloadReport, Box<dyn std::error::Error>> {
+    let mut engine = Engine::new();
+    let mut kinds = HashSet::new();
+    for definition in &workload.workflows {
+        for step in definition.steps.values() {
+            kinds.insert(step.kind.clone());
+        }
+    }
+    for kind in kinds {
+        let latency_ms = workload.step_latencies_ms.get(&kind).copied().unwrap_or(0);
+        engine.register_executor(
+            kind,
+            Arc::new(NoopExecutor {
+                latency: Duration::from_millis(latency_ms),
+            }),
+        );
+    }
+    let engine = Arc::new(engine);
+
+    let collected = Arc::new(Mutex::new(Collected::default()));
+    let mut events = engine.subscribe();
+    let collector = collected.clone();
+    let collector_task = tokio::spawn(async move {
+        while let Some(notification) = events.next().await {
+            if let WorkflowNotification::Event(event) = notification {
+                collector.lock().await.record(&event);
+            }
+        }
+    });
+
+    let wall_start = Instant::now();
+    let mut handles = Vec::new();
+    for definition in workload.workflows {
+        for _ in 0..workload.concurrent_runs {
+            let engine = engine.clone();
+            let definition = definition.clone();
+            handles.push(tokio::spawn(async move { engine.start(&definition).await }));
+        }
+    }
+    let total_workflows = handles.len() as u64;
+    for handle in handles {
+        handle.await??;
+    }
+    let wall_time = wall_start.elapsed();
+
+    // Let the collector drain any events still in flight before reading it.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    collector_task.abort();
+    let collected = collected.lock().await;
+
+    let wall_time_ms = wall_time.as_secs_f64() * 1000.0;
+    Ok(WorkloadReport {
+        name: workload.name,
+        total_workflows,
+        wall_time_ms,
+        workflows_per_sec: if wall_time_ms > 0.0 {
+            total_workflows as f64 / (wall_time_ms / 1000.0)
+        } else {
+            0.0
+        },
+        step_latency: percentiles(collected.step_latencies_ms.clone()),
+        end_to_end_latency: percentiles(collected.end_to_end_ms.clone()),
+        retries: collected.retries,
+    })
+}
+
+/// Folds the engine's event stream into the samples a workload report needs:
+/// step and end-to-end latencies (from paired state-transition timestamps)
+/// and a retry count.
+#[derive(Default)]
+struct Collected {
+    step_started_at: HashMap<(Uuid, String), DateTime<Utc>>,
+    step_latencies_ms: Vec<f64>,
+    run_started_at: HashMap<Uuid, DateTime<Utc>>,
+    end_to_end_ms: Vec<f64>,
+    retries: u64,
+}
+
+impl Collected {
+    fn record(&mut self, event: &EngineEvent) {
+        match event {
+            EngineEvent::Workflow(event) => {
+                if event.to == WorkflowState::Running && event.from == WorkflowState::Created {
+                    self.run_started_at.insert(event.run_id, event.timestamp);
+                } else if matches!(event.to, WorkflowState::Completed | WorkflowState::Failed) {
+                    if let Some(started_at) = self.run_started_at.remove(&event.run_id) {
+                        self.end_to_end_ms.push(millis_between(started_at, event.timestamp));
+                    }
+                }
+            }
+            EngineEvent::Step(event) => {
+                let key = (event.run_id, event.step_id.clone());
+                match (&event.from, &event.to) {
+                    (StepState::Queued, StepState::Running) => {
+                        self.step_started_at.insert(key, event.timestamp);
+                    }
+                    (StepState::Running, StepState::Running) => self.retries += 1,
+                    (StepState::Running, StepState::Succeeded) | (StepState::Running, StepState::Failed) => {
+                        if let Some(started_at) = self.step_started_at.remove(&key) {
+                            self.step_latencies_ms.push(millis_between(started_at, event.timestamp));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+fn millis_between(start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    (end - start).num_milliseconds().max(0) as f64
+}
+
+fn percentiles(mut samples: Vec<f64>) -> Percentiles {
+    if samples.is_empty() {
+        return Percentiles::default();
+    }
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    Percentiles {
+        p50_ms: percentile_at(&samples, 0.50),
+        p95_ms: percentile_at(&samples, 0.95),
+        p99_ms: percentile_at(&samples, 0.99),
+    }
+}
+
+fn percentile_at(sorted: &[f64], p: f64) -> f64 {
+    let index = (((sorted.len() - 1) as f64) * p).round() as usize;
+    sorted[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_of_empty_samples_are_zero() {
+        let result = percentiles(Vec::new());
+        assert_eq!(result.p50_ms, 0.0);
+        assert_eq!(result.p95_ms, 0.0);
+        assert_eq!(result.p99_ms, 0.0);
+    }
+
+    #[test]
+    fn percentiles_of_a_single_sample_are_that_sample() {
+        let result = percentiles(vec![42.0]);
+        assert_eq!(result.p50_ms, 42.0);
+        assert_eq!(result.p99_ms, 42.0);
+    }
+
+    #[test]
+    fn percentiles_pick_the_expected_rank_out_of_order() {
+        let samples: Vec<f64> = (1..=100).rev().map(|n| n as f64).collect();
+        let result = percentiles(samples);
+        // index = round((len - 1) * p), so p50 over 100 samples lands on
+        // rank 51 (index 50), not the naive "50th" value.
+        assert_eq!(result.p50_ms, 51.0);
+        assert_eq!(result.p95_ms, 95.0);
+        assert_eq!(result.p99_ms, 99.0);
+    }
+
+    #[test]
+    fn percentile_at_is_monotonic_with_p() {
+        let sorted = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        assert!(percentile_at(&sorted, 0.0) <= percentile_at(&sorted, 0.5));
+        assert!(percentile_at(&sorted, 0.5) <= percentile_at(&sorted, 1.0));
+    }
+}
@@ -0,0 +1,264 @@
+use crate::model::EngineError;
+use serde_json::Value;
+
+/// Evaluates a small boolean expression (`==`, `!=`, `<`, `>`, `&&`, `||`, dotted
+/// paths) against a run-scoped JSON context. `None` conditions are handled by the
+/// caller as unconditional edges; this only evaluates the `Some` case.
+pub fn evaluate(condition: &str, context: &Value) -> Result<bool, EngineError> {
+    let tokens = tokenize(condition)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let result = parser.parse_or(context)?;
+    if parser.pos != parser.tokens.len() {
+        return Err(EngineError::InvalidCondition(condition.to_string()));
+    }
+    Ok(result)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Path(String),
+    Literal(Value),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, EngineError> {
+    let bad = || EngineError::InvalidCondition(src.to_string());
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if c == '"' {
+            let mut s = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                s.push(chars[i]);
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(bad());
+            }
+            i += 1;
+            tokens.push(Token::Literal(Value::String(s)));
+        } else if c == '&' && chars.get(i + 1) == Some(&'&') {
+            tokens.push(Token::Op("&&"));
+            i += 2;
+        } else if c == '|' && chars.get(i + 1) == Some(&'|') {
+            tokens.push(Token::Op("||"));
+            i += 2;
+        } else if c == '=' && chars.get(i + 1) == Some(&'=') {
+            tokens.push(Token::Op("=="));
+            i += 2;
+        } else if c == '!' && chars.get(i + 1) == Some(&'=') {
+            tokens.push(Token::Op("!="));
+            i += 2;
+        } else if c == '<' {
+            tokens.push(Token::Op("<"));
+            i += 1;
+        } else if c == '>' {
+            tokens.push(Token::Op(">"));
+            i += 1;
+        } else if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.' || chars[i] == '-')
+            {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            tokens.push(classify_word(&word));
+        } else {
+            return Err(bad());
+        }
+    }
+    Ok(tokens)
+}
+
+fn classify_word(word: &str) -> Token {
+    match word {
+        "true" => Token::Literal(Value::Bool(true)),
+        "false" => Token::Literal(Value::Bool(false)),
+        _ => {
+            if let Ok(n) = word.parse::<f64>() {
+                Token::Literal(Value::from(n))
+            } else {
+                Token::Path(word.to_string())
+            }
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_or(&mut self, ctx: &Value) -> Result<bool, EngineError> {
+        let mut result = self.parse_and(ctx)?;
+        while matches!(self.peek(), Some(Token::Op("||"))) {
+            self.pos += 1;
+            let rhs = self.parse_and(ctx)?;
+            result = result || rhs;
+        }
+        Ok(result)
+    }
+
+    fn parse_and(&mut self, ctx: &Value) -> Result<bool, EngineError> {
+        let mut result = self.parse_cmp(ctx)?;
+        while matches!(self.peek(), Some(Token::Op("&&"))) {
+            self.pos += 1;
+            let rhs = self.parse_cmp(ctx)?;
+            result = result && rhs;
+        }
+        Ok(result)
+    }
+
+    fn parse_cmp(&mut self, ctx: &Value) -> Result<bool, EngineError> {
+        if matches!(self.peek(), Some(Token::LParen)) {
+            self.pos += 1;
+            let result = self.parse_or(ctx)?;
+            match self.peek() {
+                Some(Token::RParen) => self.pos += 1,
+                _ => return Err(EngineError::InvalidCondition("unmatched (".to_string())),
+            }
+            return Ok(result);
+        }
+
+        let lhs = self.parse_operand(ctx)?;
+        match self.peek() {
+            Some(Token::Op(op @ ("==" | "!=" | "<" | ">"))) => {
+                let op = *op;
+                self.pos += 1;
+                let rhs = self.parse_operand(ctx)?;
+                Ok(compare(op, &lhs, &rhs))
+            }
+            _ => Ok(truthy(&lhs)),
+        }
+    }
+
+    fn parse_operand(&mut self, ctx: &Value) -> Result<Value, EngineError> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Literal(v)) => {
+                self.pos += 1;
+                Ok(v.clone())
+            }
+            Some(Token::Path(path)) => {
+                self.pos += 1;
+                Ok(resolve_path(ctx, path))
+            }
+            other => Err(EngineError::InvalidCondition(format!("{other:?}"))),
+        }
+    }
+}
+
+fn resolve_path(ctx: &Value, path: &str) -> Value {
+    let mut current = ctx;
+    for segment in path.split('.') {
+        match current.get(segment) {
+            Some(next) => current = next,
+            None => return Value::Null,
+        }
+    }
+    current.clone()
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().map(|n| n != 0.0).unwrap_or(false),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+fn compare(op: &str, lhs: &Value, rhs: &Value) -> bool {
+    match op {
+        "==" => values_equal(lhs, rhs),
+        "!=" => !values_equal(lhs, rhs),
+        "<" | ">" => {
+            let ordering = match (lhs.as_f64(), rhs.as_f64()) {
+                (Some(a), Some(b)) => a.partial_cmp(&b),
+                _ => lhs.as_str().and_then(|a| rhs.as_str().map(|b| a.cmp(b))),
+            };
+            match ordering {
+                Some(std::cmp::Ordering::Less) => op == "<",
+                Some(std::cmp::Ordering::Greater) => op == ">",
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Numbers compare by value regardless of how they were parsed (an integer
+/// step output like `3` must equal the literal `3`, parsed as `3.0`).
+/// Everything else falls back to `Value`'s own equality.
+fn values_equal(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs.as_f64(), rhs.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => lhs == rhs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn integer_output_equals_numeric_literal() {
+        let ctx = json!({"stepA": {"output": {"count": 3}}});
+        assert!(evaluate("stepA.output.count == 3", &ctx).unwrap());
+        assert!(!evaluate("stepA.output.count != 3", &ctx).unwrap());
+    }
+
+    #[test]
+    fn string_equality_and_inequality() {
+        let ctx = json!({"stepA": {"output": "ready"}});
+        assert!(evaluate("stepA.output == \"ready\"", &ctx).unwrap());
+        assert!(evaluate("stepA.output != \"pending\"", &ctx).unwrap());
+    }
+
+    #[test]
+    fn numeric_ordering() {
+        let ctx = json!({"stepA": {"output": {"count": 5}}});
+        assert!(evaluate("stepA.output.count > 3", &ctx).unwrap());
+        assert!(!evaluate("stepA.output.count < 3", &ctx).unwrap());
+    }
+
+    #[test]
+    fn and_or_and_parens() {
+        let ctx = json!({"a": {"output": 1}, "b": {"output": 0}});
+        assert!(evaluate("a.output == 1 && (b.output == 0 || b.output == 1)", &ctx).unwrap());
+        assert!(!evaluate("a.output == 0 && b.output == 0", &ctx).unwrap());
+    }
+
+    #[test]
+    fn missing_path_is_null_and_falsy() {
+        let ctx = json!({});
+        assert!(!evaluate("missing.output", &ctx).unwrap());
+    }
+
+    #[test]
+    fn invalid_condition_is_an_error() {
+        let ctx = json!({});
+        assert!(evaluate("== ==", &ctx).is_err());
+    }
+}
@@ -0,0 +1,526 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use rand::Rng;
+use serde_json::Value;
+use tokio::sync::{broadcast, Mutex};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::condition;
+use crate::events::{EngineEvent, SubscriptionFilter, WorkflowEvent, WorkflowLifecycleEvent, WorkflowNotification};
+use crate::history::{self, HistoryEvent, SharedHistoryStore};
+use crate::model::{EngineError, RetryPolicy, Step, StepState, Transition, WorkflowDefinition, WorkflowState};
+use crate::queue::{ReadyStep, TaskQueue};
+
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Executes a single step `kind`, turning its `config` into an output value or
+/// an error. Implementations are the boundary between the engine and the
+/// outside world (HTTP calls, queues, local computation, ...).
+#[async_trait]
+pub trait StepExecutor: Send + Sync {
+    async fn execute(&self, config: &Value) -> Result<Value, EngineError>;
+}
+
+/// Snapshot of a single workflow run: its overall state plus the state and
+/// completed output of every step visited so far.
+#[derive(Debug, Clone)]
+pub struct RunState {
+    pub run_id: Uuid,
+    pub workflow_id: String,
+    pub state: WorkflowState,
+    pub step_states: HashMap<String, StepState>,
+    pub outputs: HashMap<String, Value>,
+    pub attempts: HashMap<String, u32>,
+    pub last_errors: HashMap<String, String>,
+}
+
+pub struct Engine {
+    executors: HashMap<String, Arc<dyn StepExecutor>>,
+    runs: Mutex<HashMap<Uuid, RunState>>,
+    history: Option<SharedHistoryStore>,
+    events: broadcast::Sender<EngineEvent>,
+    queue: Option<Arc<TaskQueue>>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            executors: HashMap::new(),
+            runs: Mutex::new(HashMap::new()),
+            history: None,
+            events,
+            queue: None,
+        }
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the executor responsible for steps of the given `kind`.
+    pub fn register_executor(&mut self, kind: impl Into<String>, executor: Arc<dyn StepExecutor>) {
+        self.executors.insert(kind.into(), executor);
+    }
+
+    /// Configures the store used to persist run history. Without one, runs
+    /// execute normally but cannot be replayed or resumed after a crash.
+    pub fn set_history_store(&mut self, store: SharedHistoryStore) {
+        self.history = Some(store);
+    }
+
+    /// Configures a task queue used to dispatch steps whose `kind` has no
+    /// locally registered `StepExecutor`, handing them to remote workers
+    /// over the pull-based worker protocol instead.
+    pub fn set_task_queue(&mut self, queue: Arc<TaskQueue>) {
+        self.queue = Some(queue);
+    }
+
+    /// Subscribes to every step and workflow state change across all
+    /// workflows and runs.
+    pub fn subscribe(&self) -> impl Stream<Item = WorkflowNotification> {
+        self.subscribe_filtered(SubscriptionFilter::default())
+    }
+
+    /// Subscribes to step and workflow state changes matching `filter`, dropping events
+    /// that don't match rather than delivering them. If this subscriber
+    /// falls too far behind the broadcast channel, a `Lagged` notification
+    /// is delivered in place of the events that were dropped.
+    pub fn subscribe_filtered(&self, filter: SubscriptionFilter) -> impl Stream<Item = WorkflowNotification> {
+        BroadcastStream::new(self.events.subscribe()).filter_map(move |item| match item {
+            Ok(event) if filter.matches(&event) => Some(WorkflowNotification::Event(event)),
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => Some(WorkflowNotification::Lagged { skipped }),
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn publish_step(
+        &self,
+        run_id: Uuid,
+        workflow_id: &str,
+        step_id: &str,
+        step_kind: &str,
+        from: StepState,
+        to: StepState,
+        attempt: u32,
+    ) {
+        let _ = self.events.send(EngineEvent::Step(WorkflowEvent {
+            run_id,
+            workflow_id: workflow_id.to_string(),
+            step_id: step_id.to_string(),
+            step_kind: step_kind.to_string(),
+            from,
+            to,
+            attempt,
+            timestamp: Utc::now(),
+        }));
+    }
+
+    fn publish_workflow(&self, run_id: Uuid, workflow_id: &str, from: WorkflowState, to: WorkflowState) {
+        let _ = self.events.send(EngineEvent::Workflow(WorkflowLifecycleEvent {
+            run_id,
+            workflow_id: workflow_id.to_string(),
+            from,
+            to,
+            timestamp: Utc::now(),
+        }));
+    }
+
+    /// Returns a snapshot of a run's state, if it exists.
+    pub async fn run_state(&self, run_id: Uuid) -> Option<RunState> {
+        self.runs.lock().await.get(&run_id).cloned()
+    }
+
+    /// Drives `definition` from its entry step through to completion,
+    /// dispatching each step to the registered `StepExecutor` and following
+    /// transitions whose condition is satisfied by the run's context.
+    pub async fn start(&self, definition: &WorkflowDefinition) -> Result<Uuid, EngineError> {
+        let run_id = Uuid::new_v4();
+        let mut run = RunState {
+            run_id,
+            workflow_id: definition.id.clone(),
+            state: WorkflowState::Running,
+            step_states: definition
+                .steps
+                .keys()
+                .map(|id| (id.clone(), StepState::Idle))
+                .collect(),
+            outputs: HashMap::new(),
+            attempts: HashMap::new(),
+            last_errors: HashMap::new(),
+        };
+
+        self.emit(
+            run_id,
+            HistoryEvent::WorkflowStarted {
+                workflow_id: definition.id.clone(),
+            },
+        )
+        .await?;
+        self.publish_workflow(run_id, &definition.id, WorkflowState::Created, WorkflowState::Running);
+
+        let result = self.drive_from(definition, &mut run, definition.entry.clone()).await;
+
+        let previous_state = run.state.clone();
+        run.state = match &result {
+            Ok(()) => WorkflowState::Completed,
+            Err(_) => WorkflowState::Failed,
+        };
+        self.publish_workflow(run_id, &definition.id, previous_state, run.state.clone());
+        self.runs.lock().await.insert(run_id, run);
+        result.map(|()| run_id)
+    }
+
+    /// Replays `run_id`'s persisted history to reconstruct its state and
+    /// continues execution from the step after the last one it completed.
+    /// Requires a history store to have been configured.
+    pub async fn resume(&self, run_id: Uuid, definition: &WorkflowDefinition) -> Result<Uuid, EngineError> {
+        let store = self
+            .history
+            .clone()
+            .ok_or_else(|| EngineError::Other(anyhow::anyhow!("no history store configured")))?;
+        let replayed = history::replay(store.as_ref(), run_id).await?;
+
+        if matches!(replayed.state, WorkflowState::Completed | WorkflowState::Failed) {
+            return Ok(run_id);
+        }
+
+        let mut run = RunState {
+            run_id,
+            workflow_id: replayed.workflow_id.unwrap_or_else(|| definition.id.clone()),
+            state: WorkflowState::Running,
+            step_states: replayed.step_states,
+            outputs: replayed.outputs,
+            attempts: HashMap::new(),
+            last_errors: HashMap::new(),
+        };
+
+        let start = match &replayed.last_completed_step {
+            Some(last_step) => {
+                let step = definition.steps.get(last_step).ok_or(EngineError::InvalidTransition)?;
+                next_step(&step.transitions, &run.outputs)?.ok_or(EngineError::InvalidTransition)?
+            }
+            None => definition.entry.clone(),
+        };
+
+        let result = self.drive_from(definition, &mut run, start).await;
+
+        let previous_state = run.state.clone();
+        run.state = match &result {
+            Ok(()) => WorkflowState::Completed,
+            Err(_) => WorkflowState::Failed,
+        };
+        self.publish_workflow(run_id, &run.workflow_id, previous_state, run.state.clone());
+        self.runs.lock().await.insert(run_id, run);
+        result.map(|()| run_id)
+    }
+
+    async fn drive_from(
+        &self,
+        definition: &WorkflowDefinition,
+        run: &mut RunState,
+        start: String,
+    ) -> Result<(), EngineError> {
+        let mut current = start;
+        let mut visited = HashSet::new();
+
+        loop {
+            if !visited.insert(current.clone()) {
+                return Err(EngineError::InvalidTransition);
+            }
+
+            let step = definition
+                .steps
+                .get(&current)
+                .ok_or(EngineError::InvalidTransition)?;
+
+            run.step_states.insert(step.id.clone(), StepState::Queued);
+            self.emit(run.run_id, HistoryEvent::StepQueued { step_id: step.id.clone() })
+                .await?;
+            self.publish_step(
+                run.run_id,
+                &run.workflow_id,
+                &step.id,
+                &step.kind,
+                StepState::Idle,
+                StepState::Queued,
+                1,
+            );
+
+            run.step_states.insert(step.id.clone(), StepState::Running);
+            self.emit(run.run_id, HistoryEvent::StepStarted { step_id: step.id.clone() })
+                .await?;
+            self.publish_step(
+                run.run_id,
+                &run.workflow_id,
+                &step.id,
+                &step.kind,
+                StepState::Queued,
+                StepState::Running,
+                1,
+            );
+
+            let dispatch = match self.executors.get(&step.kind) {
+                Some(executor) => Dispatch::Local(executor.as_ref()),
+                None => match &self.queue {
+                    Some(queue) => Dispatch::Queued(queue.as_ref()),
+                    None => return Err(EngineError::UnknownStepKind(step.kind.clone())),
+                },
+            };
+
+            match self.execute_with_retry(step, &dispatch, run).await {
+                Ok(output) => {
+                    run.step_states.insert(step.id.clone(), StepState::Succeeded);
+                    self.emit(
+                        run.run_id,
+                        HistoryEvent::StepSucceeded {
+                            step_id: step.id.clone(),
+                            output: output.clone(),
+                        },
+                    )
+                    .await?;
+                    let attempt = run.attempts.get(&step.id).copied().unwrap_or(1);
+                    self.publish_step(
+                        run.run_id,
+                        &run.workflow_id,
+                        &step.id,
+                        &step.kind,
+                        StepState::Running,
+                        StepState::Succeeded,
+                        attempt,
+                    );
+                    run.outputs.insert(step.id.clone(), output);
+                }
+                Err(err) => {
+                    run.step_states.insert(step.id.clone(), StepState::Failed);
+                    self.emit(
+                        run.run_id,
+                        HistoryEvent::StepFailed {
+                            step_id: step.id.clone(),
+                            error: err.to_string(),
+                        },
+                    )
+                    .await?;
+                    let attempt = run.attempts.get(&step.id).copied().unwrap_or(1);
+                    self.publish_step(
+                        run.run_id,
+                        &run.workflow_id,
+                        &step.id,
+                        &step.kind,
+                        StepState::Running,
+                        StepState::Failed,
+                        attempt,
+                    );
+                    return Err(err);
+                }
+            }
+            info!(step = %step.id, "step succeeded");
+
+            match next_step(&step.transitions, &run.outputs)? {
+                Some(to) => {
+                    self.emit(
+                        run.run_id,
+                        HistoryEvent::TransitionTaken {
+                            from: step.id.clone(),
+                            to: to.clone(),
+                        },
+                    )
+                    .await?;
+                    current = to;
+                }
+                None => {
+                    self.emit(run.run_id, HistoryEvent::WorkflowCompleted).await?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    async fn emit(&self, run_id: Uuid, event: HistoryEvent) -> Result<(), EngineError> {
+        match &self.history {
+            Some(store) => store.append(run_id, event).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Runs a step, honoring its `RetryPolicy` on failure. Records the
+    /// attempt count and last error on the run so they surface in state
+    /// queries even when the step ultimately succeeds after retrying.
+    async fn execute_with_retry(&self, step: &Step, dispatch: &Dispatch<'_>, run: &mut RunState) -> Result<Value, EngineError> {
+        let mut attempt = 1u32;
+        loop {
+            match dispatch.run(step, run.run_id).await {
+                Ok(output) => {
+                    run.attempts.insert(step.id.clone(), attempt);
+                    return Ok(output);
+                }
+                Err(err) => {
+                    run.attempts.insert(step.id.clone(), attempt);
+                    run.last_errors.insert(step.id.clone(), err.to_string());
+
+                    let Some(policy) = &step.retry else {
+                        return Err(err);
+                    };
+                    if policy
+                        .non_retryable_errors
+                        .iter()
+                        .any(|pattern| err.to_string().contains(pattern.as_str()))
+                    {
+                        return Err(err);
+                    }
+                    if attempt >= policy.max_attempts {
+                        return Err(err);
+                    }
+
+                    let delay = backoff_delay(policy, attempt);
+                    warn!(step = %step.id, attempt, delay_ms = delay.as_millis() as u64, "step failed, retrying");
+                    self.publish_step(
+                        run.run_id,
+                        &run.workflow_id,
+                        &step.id,
+                        &step.kind,
+                        StepState::Running,
+                        StepState::Running,
+                        attempt + 1,
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// How a step's `kind` gets run: by a locally registered `StepExecutor`, or
+/// by handing it to a remote worker through the `TaskQueue`.
+enum Dispatch<'a> {
+    Local(&'a dyn StepExecutor),
+    Queued(&'a TaskQueue),
+}
+
+impl Dispatch<'_> {
+    async fn run(&self, step: &Step, run_id: Uuid) -> Result<Value, EngineError> {
+        match self {
+            Dispatch::Local(executor) => executor.execute(&step.config).await,
+            Dispatch::Queued(queue) => {
+                queue
+                    .submit(ReadyStep {
+                        run_id,
+                        step_id: step.id.clone(),
+                        kind: step.kind.clone(),
+                        config: step.config.clone(),
+                    })
+                    .await
+            }
+        }
+    }
+}
+
+/// `min(initial_interval_ms * backoff_coefficient^(attempt-1), max_interval_ms)`
+/// with up to ±10% jitter applied.
+fn backoff_delay(policy: &RetryPolicy, attempt: u32) -> Duration {
+    let base = policy.initial_interval_ms as f64 * policy.backoff_coefficient.powi((attempt - 1) as i32);
+    let capped = base.min(policy.max_interval_ms as f64);
+    let jitter = rand::thread_rng().gen_range(-0.1..=0.1);
+    let jittered = (capped * (1.0 + jitter)).max(0.0);
+    Duration::from_millis(jittered as u64)
+}
+
+/// Follows the first transition whose condition is satisfied, or `None` if
+/// no transition matches (including a terminal step with no transitions).
+fn next_step(transitions: &[Transition], outputs: &HashMap<String, Value>) -> Result<Option<String>, EngineError> {
+    let context = build_context(outputs);
+    for transition in transitions {
+        let matched = match &transition.condition {
+            None => true,
+            Some(condition) => condition::evaluate(condition, &context)?,
+        };
+        if matched {
+            return Ok(Some(transition.to.clone()));
+        }
+    }
+    Ok(None)
+}
+
+fn build_context(outputs: &HashMap<String, Value>) -> Value {
+    let mut map = serde_json::Map::new();
+    for (step_id, output) in outputs {
+        let mut entry = serde_json::Map::new();
+        entry.insert("output".to_string(), output.clone());
+        map.insert(step_id.clone(), Value::Object(entry));
+    }
+    Value::Object(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_interval_ms: 100,
+            backoff_coefficient: 2.0,
+            max_interval_ms: 1_000,
+            non_retryable_errors: Vec::new(),
+        }
+    }
+
+    /// `backoff_delay` jitters by up to ±10%, so assert the result falls in
+    /// the expected band around `min(base, max_interval_ms)` rather than an
+    /// exact value.
+    fn assert_in_jitter_band(delay: Duration, expected_base_ms: f64) {
+        let delay_ms = delay.as_millis() as f64;
+        assert!(
+            delay_ms >= expected_base_ms * 0.9 && delay_ms <= expected_base_ms * 1.1,
+            "{delay_ms} not within ±10% of {expected_base_ms}"
+        );
+    }
+
+    #[test]
+    fn backoff_delay_grows_exponentially() {
+        let policy = policy();
+        assert_in_jitter_band(backoff_delay(&policy, 1), 100.0);
+        assert_in_jitter_band(backoff_delay(&policy, 2), 200.0);
+        assert_in_jitter_band(backoff_delay(&policy, 3), 400.0);
+    }
+
+    #[test]
+    fn backoff_delay_caps_at_max_interval() {
+        let policy = policy();
+        assert_in_jitter_band(backoff_delay(&policy, 10), 1_000.0);
+    }
+
+    #[test]
+    fn next_step_follows_first_matching_condition() {
+        let outputs = HashMap::from([("a".to_string(), Value::from(1))]);
+        let transitions = vec![
+            Transition {
+                to: "skip".to_string(),
+                condition: Some("a.output == 0".to_string()),
+            },
+            Transition {
+                to: "take".to_string(),
+                condition: Some("a.output == 1".to_string()),
+            },
+        ];
+        assert_eq!(next_step(&transitions, &outputs).unwrap(), Some("take".to_string()));
+    }
+
+    #[test]
+    fn next_step_returns_none_when_terminal() {
+        let outputs = HashMap::new();
+        assert_eq!(next_step(&[], &outputs).unwrap(), None);
+    }
+}
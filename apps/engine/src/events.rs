@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::model::{StepState, WorkflowState};
+
+/// A single step state transition, published on the engine's broadcast
+/// channel as it happens. `attempt` is the retry attempt this transition
+/// belongs to (1 for the first try); a `Running -> Running` event with
+/// `attempt > 1` marks a retry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowEvent {
+    pub run_id: Uuid,
+    pub workflow_id: String,
+    pub step_id: String,
+    pub step_kind: String,
+    pub from: StepState,
+    pub to: StepState,
+    pub attempt: u32,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// An overall workflow state transition (e.g. `Running -> Completed`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowLifecycleEvent {
+    pub run_id: Uuid,
+    pub workflow_id: String,
+    pub from: WorkflowState,
+    pub to: WorkflowState,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Everything the engine publishes on its broadcast channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EngineEvent {
+    Step(WorkflowEvent),
+    Workflow(WorkflowLifecycleEvent),
+}
+
+impl EngineEvent {
+    pub fn run_id(&self) -> Uuid {
+        match self {
+            EngineEvent::Step(event) => event.run_id,
+            EngineEvent::Workflow(event) => event.run_id,
+        }
+    }
+
+    pub fn workflow_id(&self) -> &str {
+        match self {
+            EngineEvent::Step(event) => &event.workflow_id,
+            EngineEvent::Workflow(event) => &event.workflow_id,
+        }
+    }
+}
+
+/// An item delivered to a subscriber: either an event, or a marker that some
+/// events were dropped because the subscriber fell behind.
+#[derive(Debug, Clone)]
+pub enum WorkflowNotification {
+    Event(EngineEvent),
+    Lagged { skipped: u64 },
+}
+
+/// Restricts a subscription to events for a single workflow and/or run.
+/// An unset field matches anything.
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionFilter {
+    pub workflow_id: Option<String>,
+    pub run_id: Option<Uuid>,
+}
+
+impl SubscriptionFilter {
+    pub fn by_workflow(workflow_id: impl Into<String>) -> Self {
+        Self {
+            workflow_id: Some(workflow_id.into()),
+            run_id: None,
+        }
+    }
+
+    pub fn by_run(run_id: Uuid) -> Self {
+        Self {
+            workflow_id: None,
+            run_id: Some(run_id),
+        }
+    }
+
+    pub(crate) fn matches(&self, event: &EngineEvent) -> bool {
+        if let Some(workflow_id) = &self.workflow_id {
+            if workflow_id != event.workflow_id() {
+                return false;
+            }
+        }
+        if let Some(run_id) = self.run_id {
+            if run_id != event.run_id() {
+                return false;
+            }
+        }
+        true
+    }
+}
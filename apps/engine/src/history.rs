@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::model::{EngineError, StepState, WorkflowState};
+
+/// Schema version of [`HistoryEvent`] as currently defined. Bump this and add
+/// a case to [`upgrade`] whenever the event shapes change.
+pub const CURRENT_VERSION: u16 = 1;
+
+/// A single state-changing fact about a run. The full sequence of these,
+/// folded in order, reconstructs a run's state; nothing else is authoritative.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HistoryEvent {
+    WorkflowStarted { workflow_id: String },
+    StepQueued { step_id: String },
+    StepStarted { step_id: String },
+    StepSucceeded { step_id: String, output: Value },
+    StepFailed { step_id: String, error: String },
+    TransitionTaken { from: String, to: String },
+    WorkflowCompleted,
+}
+
+/// Upgrades a serialized event one schema version at a time. Each arm
+/// transforms the wire bytes from `version` to `version + 1`; chaining these
+/// in [`Migrate::migrate`] lets old histories stay loadable after the schema
+/// evolves.
+fn upgrade(version: u16, _bytes: &[u8]) -> Result<Vec<u8>, EngineError> {
+    Err(EngineError::Other(anyhow::anyhow!(
+        "no migration registered from history event version {version}"
+    )))
+}
+
+pub trait Migrate: Sized + serde::de::DeserializeOwned {
+    fn migrate(old_version: u16, bytes: &[u8]) -> Result<Self, EngineError> {
+        let mut version = old_version;
+        let mut data = bytes.to_vec();
+        while version < CURRENT_VERSION {
+            data = upgrade(version, &data)?;
+            version += 1;
+        }
+        serde_json::from_slice(&data).map_err(|e| EngineError::Other(e.into()))
+    }
+}
+
+impl Migrate for HistoryEvent {}
+
+/// A `HistoryEvent` plus the version it was written with, as persisted by a
+/// `HistoryStore`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedEvent {
+    pub version: u16,
+    pub event: HistoryEvent,
+}
+
+impl PersistedEvent {
+    fn new(event: HistoryEvent) -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            event,
+        }
+    }
+}
+
+/// Pluggable append-only persistence for run history.
+#[async_trait]
+pub trait HistoryStore: Send + Sync {
+    async fn append(&self, run_id: Uuid, event: HistoryEvent) -> Result<(), EngineError>;
+    async fn load(&self, run_id: Uuid) -> Result<Vec<HistoryEvent>, EngineError>;
+}
+
+/// In-memory `HistoryStore`, useful for tests and for engines that don't
+/// need to survive a restart.
+#[derive(Default)]
+pub struct InMemoryHistoryStore {
+    runs: Mutex<HashMap<Uuid, Vec<PersistedEvent>>>,
+}
+
+impl InMemoryHistoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl HistoryStore for InMemoryHistoryStore {
+    async fn append(&self, run_id: Uuid, event: HistoryEvent) -> Result<(), EngineError> {
+        self.runs
+            .lock()
+            .await
+            .entry(run_id)
+            .or_default()
+            .push(PersistedEvent::new(event));
+        Ok(())
+    }
+
+    async fn load(&self, run_id: Uuid) -> Result<Vec<HistoryEvent>, EngineError> {
+        Ok(self
+            .runs
+            .lock()
+            .await
+            .get(&run_id)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|persisted| persisted.event)
+            .collect())
+    }
+}
+
+/// `HistoryStore` backed by one newline-delimited JSON file per run under
+/// `base_dir`.
+pub struct FileHistoryStore {
+    base_dir: PathBuf,
+}
+
+impl FileHistoryStore {
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+        }
+    }
+
+    fn path_for(&self, run_id: Uuid) -> PathBuf {
+        self.base_dir.join(format!("{run_id}.jsonl"))
+    }
+}
+
+#[async_trait]
+impl HistoryStore for FileHistoryStore {
+    async fn append(&self, run_id: Uuid, event: HistoryEvent) -> Result<(), EngineError> {
+        fs::create_dir_all(&self.base_dir)
+            .await
+            .map_err(|e| EngineError::Other(e.into()))?;
+        let line = serde_json::to_string(&PersistedEvent::new(event))
+            .map_err(|e| EngineError::Other(e.into()))?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path_for(run_id))
+            .await
+            .map_err(|e| EngineError::Other(e.into()))?;
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|e| EngineError::Other(e.into()))?;
+        file.write_all(b"\n").await.map_err(|e| EngineError::Other(e.into()))?;
+        Ok(())
+    }
+
+    async fn load(&self, run_id: Uuid) -> Result<Vec<HistoryEvent>, EngineError> {
+        let path = self.path_for(run_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(path).await.map_err(|e| EngineError::Other(e.into()))?;
+        contents
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                // Only the version is read up front; the event itself may be in
+                // an older shape and is handed to `migrate` as raw bytes.
+                let raw: Value = serde_json::from_str(line).map_err(|e| EngineError::Other(e.into()))?;
+                let version = raw
+                    .get("version")
+                    .and_then(Value::as_u64)
+                    .and_then(|v| u16::try_from(v).ok())
+                    .ok_or_else(|| EngineError::Other(anyhow::anyhow!("history record has no valid version")))?;
+                let event = raw.get("event").cloned().unwrap_or(Value::Null);
+                let bytes = serde_json::to_vec(&event).map_err(|e| EngineError::Other(e.into()))?;
+                HistoryEvent::migrate(version, &bytes)
+            })
+            .collect()
+    }
+}
+
+/// The state recovered by folding a run's event stream, used to resume a
+/// crashed engine mid-run.
+#[derive(Debug, Clone, Default)]
+pub struct Replayed {
+    pub workflow_id: Option<String>,
+    pub state: WorkflowState,
+    pub step_states: HashMap<String, StepState>,
+    pub outputs: HashMap<String, Value>,
+    pub last_completed_step: Option<String>,
+}
+
+/// Rebuilds in-memory run state purely by folding the persisted event
+/// stream for `run_id`.
+pub async fn replay(store: &dyn HistoryStore, run_id: Uuid) -> Result<Replayed, EngineError> {
+    let events = store.load(run_id).await?;
+    let mut replayed = Replayed::default();
+
+    for event in events {
+        match event {
+            HistoryEvent::WorkflowStarted { workflow_id } => {
+                replayed.workflow_id = Some(workflow_id);
+                replayed.state = WorkflowState::Running;
+            }
+            HistoryEvent::StepQueued { step_id } => {
+                replayed.step_states.insert(step_id, StepState::Queued);
+            }
+            HistoryEvent::StepStarted { step_id } => {
+                replayed.step_states.insert(step_id, StepState::Running);
+            }
+            HistoryEvent::StepSucceeded { step_id, output } => {
+                replayed.step_states.insert(step_id.clone(), StepState::Succeeded);
+                replayed.outputs.insert(step_id.clone(), output);
+                replayed.last_completed_step = Some(step_id);
+            }
+            HistoryEvent::StepFailed { step_id, .. } => {
+                replayed.step_states.insert(step_id, StepState::Failed);
+                replayed.state = WorkflowState::Failed;
+            }
+            HistoryEvent::TransitionTaken { .. } => {}
+            HistoryEvent::WorkflowCompleted => {
+                replayed.state = WorkflowState::Completed;
+            }
+        }
+    }
+
+    Ok(replayed)
+}
+
+pub type SharedHistoryStore = Arc<dyn HistoryStore>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn replay_folds_a_successful_run() {
+        let store = InMemoryHistoryStore::new();
+        let run_id = Uuid::new_v4();
+        store.append(run_id, HistoryEvent::WorkflowStarted { workflow_id: "wf".to_string() }).await.unwrap();
+        store.append(run_id, HistoryEvent::StepQueued { step_id: "a".to_string() }).await.unwrap();
+        store.append(run_id, HistoryEvent::StepStarted { step_id: "a".to_string() }).await.unwrap();
+        store
+            .append(
+                run_id,
+                HistoryEvent::StepSucceeded {
+                    step_id: "a".to_string(),
+                    output: Value::from(1),
+                },
+            )
+            .await
+            .unwrap();
+        store.append(run_id, HistoryEvent::TransitionTaken { from: "a".to_string(), to: "b".to_string() }).await.unwrap();
+        store.append(run_id, HistoryEvent::WorkflowCompleted).await.unwrap();
+
+        let replayed = replay(&store, run_id).await.unwrap();
+        assert_eq!(replayed.workflow_id, Some("wf".to_string()));
+        assert_eq!(replayed.state, WorkflowState::Completed);
+        assert_eq!(replayed.step_states.get("a"), Some(&StepState::Succeeded));
+        assert_eq!(replayed.outputs.get("a"), Some(&Value::from(1)));
+        assert_eq!(replayed.last_completed_step, Some("a".to_string()));
+    }
+
+    #[tokio::test]
+    async fn replay_of_a_failed_step_marks_the_run_failed() {
+        let store = InMemoryHistoryStore::new();
+        let run_id = Uuid::new_v4();
+        store.append(run_id, HistoryEvent::WorkflowStarted { workflow_id: "wf".to_string() }).await.unwrap();
+        store.append(run_id, HistoryEvent::StepStarted { step_id: "a".to_string() }).await.unwrap();
+        store
+            .append(
+                run_id,
+                HistoryEvent::StepFailed {
+                    step_id: "a".to_string(),
+                    error: "boom".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let replayed = replay(&store, run_id).await.unwrap();
+        assert_eq!(replayed.state, WorkflowState::Failed);
+        assert_eq!(replayed.step_states.get("a"), Some(&StepState::Failed));
+        assert_eq!(replayed.last_completed_step, None);
+    }
+
+    #[tokio::test]
+    async fn replay_of_an_unknown_run_is_empty() {
+        let store = InMemoryHistoryStore::new();
+        let replayed = replay(&store, Uuid::new_v4()).await.unwrap();
+        assert_eq!(replayed.state, WorkflowState::Created);
+        assert!(replayed.step_states.is_empty());
+    }
+}
@@ -1,12 +1,196 @@
-use staterail_engine::Engine;
-use tracing_subscriber::FmtSubscriber;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::Value;
+use staterail_engine::{Engine, TaskPayload, TaskQueue, WorkflowDefinition, WorkflowNotification};
+use tokio::sync::Mutex;
+use tokio_stream::StreamExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+use uuid::Uuid;
+
+type FilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// A lease is reclaimed and re-queued if no worker reports back within this
+/// long after acquiring it.
+const VISIBILITY_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long an `acquire` long-poll holds the connection open before
+/// returning "no task ready" to the worker.
+const ACQUIRE_LONG_POLL: Duration = Duration::from_secs(30);
+
+#[derive(Clone)]
+struct AppState {
+    engine: Arc<Engine>,
+    filter_handle: Arc<FilterHandle>,
+    current_directive: Arc<Mutex<String>>,
+    queue: Arc<TaskQueue>,
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let subscriber = FmtSubscriber::builder().with_env_filter("info").finish();
-    tracing::subscriber::set_global_default(subscriber)?;
+    let default_directive = "info".to_string();
+    let (filter, filter_handle) = reload::Layer::new(EnvFilter::new(&default_directive));
+    Registry::default().with(filter).with(tracing_subscriber::fmt::layer()).init();
+
+    let queue = Arc::new(TaskQueue::new(VISIBILITY_TIMEOUT));
+    let mut engine = Engine::new();
+    engine.set_task_queue(queue.clone());
+    let engine = Arc::new(engine);
+
+    // Steps whose kind has no locally registered executor go through the
+    // queue above; this binary is purely a distributed-dispatch engine, with
+    // workers supplying every `StepExecutor` over the worker protocol.
+    if let Ok(analytics_url) = std::env::var("ANALYTICS_URL") {
+        tokio::spawn(forward_events_to_analytics(engine.clone(), analytics_url));
+    } else {
+        tracing::info!("ANALYTICS_URL not set; events will not be forwarded for metrics");
+    }
+    tracing::info!("engine ready");
+
+    let state = AppState {
+        engine,
+        filter_handle: Arc::new(filter_handle),
+        current_directive: Arc::new(Mutex::new(default_directive)),
+        queue,
+    };
+
+    let app = Router::new()
+        .route("/filter", get(get_filter).post(set_filter))
+        .route("/workflows", post(start_workflow))
+        .route("/workflows/:run_id", get(workflow_state))
+        .route("/tasks/acquire", get(acquire_task))
+        .route("/tasks/complete", post(complete_task))
+        .route("/tasks/fail", post(fail_task))
+        .with_state(state);
 
-    let engine = Engine::new();
-    engine.start().await?;
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:9091").await?;
+    tracing::info!(addr = %listener.local_addr()?, "engine control endpoint listening");
+    axum::serve(listener, app).await?;
     Ok(())
 }
+
+/// Relays every event the engine publishes to the analytics service's
+/// `/ingest` endpoint, best-effort. A down or unreachable analytics service
+/// never blocks or fails a workflow run; it just misses metrics until it's
+/// back.
+async fn forward_events_to_analytics(engine: Arc<Engine>, analytics_url: String) {
+    let client = reqwest::Client::new();
+    let ingest_url = format!("{}/ingest", analytics_url.trim_end_matches('/'));
+    let mut events = engine.subscribe();
+    while let Some(notification) = events.next().await {
+        let WorkflowNotification::Event(event) = notification else {
+            continue;
+        };
+        if let Err(err) = client.post(&ingest_url).json(&event).send().await {
+            tracing::warn!(%err, url = %ingest_url, "failed to forward event to analytics");
+        }
+    }
+}
+
+/// Drives a submitted workflow definition to completion and returns its
+/// `run_id`. Steps whose kind has no locally registered executor are
+/// dispatched over the worker protocol via the task queue.
+async fn start_workflow(
+    State(state): State<AppState>,
+    Json(definition): Json<WorkflowDefinition>,
+) -> Result<Json<Uuid>, (StatusCode, String)> {
+    state
+        .engine
+        .start(&definition)
+        .await
+        .map(Json)
+        .map_err(|err| (StatusCode::UNPROCESSABLE_ENTITY, err.to_string()))
+}
+
+async fn workflow_state(State(state): State<AppState>, Path(run_id): Path<Uuid>) -> impl IntoResponse {
+    match state.engine.run_state(run_id).await {
+        Some(run) => Json(serde_json::json!({
+            "run_id": run.run_id,
+            "workflow_id": run.workflow_id,
+            "state": run.state,
+            "step_states": run.step_states,
+        }))
+        .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Returns the directive currently in effect, e.g. `staterail_engine=debug,info`.
+async fn get_filter(State(state): State<AppState>) -> String {
+    state.current_directive.lock().await.clone()
+}
+
+/// Replaces the active filter directive, validating it parses before
+/// swapping it in. The previous directive stays in effect on failure.
+async fn set_filter(State(state): State<AppState>, body: String) -> Result<StatusCode, (StatusCode, String)> {
+    let directive = body.trim().to_string();
+    let filter = directive
+        .parse::<EnvFilter>()
+        .map_err(|err| (StatusCode::BAD_REQUEST, format!("invalid filter directive: {err}")))?;
+
+    state
+        .filter_handle
+        .reload(filter)
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, format!("failed to reload filter: {err}")))?;
+    *state.current_directive.lock().await = directive;
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+struct AcquireParams {
+    #[serde(default)]
+    wait_ms: Option<u64>,
+}
+
+/// Worker-facing long poll: blocks until a step is ready or the long-poll
+/// window elapses, whichever comes first.
+async fn acquire_task(State(state): State<AppState>, Query(params): Query<AcquireParams>) -> impl axum::response::IntoResponse {
+    let wait = params.wait_ms.map(Duration::from_millis).unwrap_or(ACQUIRE_LONG_POLL);
+    match state.queue.acquire(wait).await {
+        Some(task) => (StatusCode::OK, Json(Some(task))),
+        None => (StatusCode::NO_CONTENT, Json(None::<TaskPayload>)),
+    }
+}
+
+#[derive(Deserialize)]
+struct CompleteRequest {
+    run_id: Uuid,
+    step_id: String,
+    lease_token: u64,
+    output: Value,
+}
+
+async fn complete_task(State(state): State<AppState>, Json(body): Json<CompleteRequest>) -> Result<StatusCode, (StatusCode, String)> {
+    state
+        .queue
+        .complete(body.run_id, &body.step_id, body.lease_token, body.output)
+        .await
+        .map(|()| StatusCode::OK)
+        .map_err(|err| (StatusCode::CONFLICT, err.to_string()))
+}
+
+#[derive(Deserialize)]
+struct FailRequest {
+    run_id: Uuid,
+    step_id: String,
+    lease_token: u64,
+    error: String,
+}
+
+async fn fail_task(State(state): State<AppState>, Json(body): Json<FailRequest>) -> Result<StatusCode, (StatusCode, String)> {
+    state
+        .queue
+        .fail(body.run_id, &body.step_id, body.lease_token, body.error)
+        .await
+        .map(|()| StatusCode::OK)
+        .map_err(|err| (StatusCode::CONFLICT, err.to_string()))
+}
+
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub enum WorkflowState {
+    #[default]
+    Created,
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StepState {
+    Idle,
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transition {
+    pub to: String,
+    pub condition: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    pub id: String,
+    pub kind: String,
+    pub config: serde_json::Value,
+    pub transitions: Vec<Transition>,
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+}
+
+/// Exponential backoff retry policy for a step's executor call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_interval_ms: u64,
+    pub backoff_coefficient: f64,
+    pub max_interval_ms: u64,
+    #[serde(default)]
+    pub non_retryable_errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowDefinition {
+    pub id: String,
+    pub name: String,
+    pub version: i32,
+    /// Id of the step execution begins at.
+    pub entry: String,
+    pub steps: HashMap<String, Step>,
+}
+
+#[derive(Debug, Error)]
+pub enum EngineError {
+    #[error("unknown workflow {0}")]
+    UnknownWorkflow(String),
+    #[error("unknown step kind {0}")]
+    UnknownStepKind(String),
+    #[error("invalid transition")]
+    InvalidTransition,
+    #[error("invalid condition: {0}")]
+    InvalidCondition(String),
+    #[error("stale or unknown lease")]
+    StaleLease,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
@@ -0,0 +1,249 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{oneshot, Mutex, Notify};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::model::EngineError;
+
+/// A step ready to run, handed to whichever worker acquires it next.
+#[derive(Debug, Clone)]
+pub struct ReadyStep {
+    pub run_id: Uuid,
+    pub step_id: String,
+    pub kind: String,
+    pub config: Value,
+}
+
+/// What a long-polling worker receives from `acquire`. `lease_token` must be
+/// echoed back on `complete`/`fail` and is rejected if the lease has since
+/// expired and been handed to someone else.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskPayload {
+    pub lease_token: u64,
+    pub run_id: Uuid,
+    pub step_id: String,
+    pub kind: String,
+    pub config: Value,
+}
+
+struct Lease {
+    step: ReadyStep,
+    token: u64,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: VecDeque<ReadyStep>,
+    leased: HashMap<(Uuid, String), Lease>,
+    waiters: HashMap<(Uuid, String), oneshot::Sender<Result<Value, String>>>,
+    next_token: u64,
+}
+
+impl QueueState {
+    /// Moves any lease whose visibility timeout has passed back onto the
+    /// pending queue, so a worker that died before reporting back doesn't
+    /// strand its step forever. There is no heartbeat: a lease expires
+    /// `visibility_timeout` after `acquire` whether or not its worker is
+    /// still running the step.
+    fn reclaim_expired(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<_> = self
+            .leased
+            .iter()
+            .filter(|(_, lease)| lease.expires_at <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            if let Some(lease) = self.leased.remove(&key) {
+                self.pending.push_back(lease.step);
+            }
+        }
+    }
+}
+
+/// Coordinates handing ready steps out to remote workers over a pull-based
+/// protocol: workers long-poll `acquire`, then report back via `complete` or
+/// `fail`. A lease that isn't completed within `visibility_timeout` is
+/// re-queued for another worker to pick up.
+pub struct TaskQueue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+    visibility_timeout: Duration,
+}
+
+impl TaskQueue {
+    pub fn new(visibility_timeout: Duration) -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            notify: Notify::new(),
+            visibility_timeout,
+        }
+    }
+
+    /// Enqueues `step` and waits for a worker to report its result. Used by
+    /// the engine in place of calling a local `StepExecutor` directly.
+    pub async fn submit(&self, step: ReadyStep) -> Result<Value, EngineError> {
+        let key = (step.run_id, step.step_id.clone());
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut state = self.state.lock().await;
+            state.waiters.insert(key, tx);
+            state.pending.push_back(step);
+        }
+        self.notify.notify_one();
+
+        rx.await
+            .map_err(|_| EngineError::Other(anyhow::anyhow!("worker never reported a result for the step")))?
+            .map_err(|error| EngineError::Other(anyhow::anyhow!(error)))
+    }
+
+    /// Long-polls for up to `wait` for a ready step, returning `None` if
+    /// none became available in time.
+    pub async fn acquire(&self, wait: Duration) -> Option<TaskPayload> {
+        let deadline = Instant::now() + wait;
+        loop {
+            {
+                let mut state = self.state.lock().await;
+                state.reclaim_expired();
+                if let Some(step) = state.pending.pop_front() {
+                    let token = state.next_token;
+                    state.next_token += 1;
+                    let key = (step.run_id, step.step_id.clone());
+                    let payload = TaskPayload {
+                        lease_token: token,
+                        run_id: step.run_id,
+                        step_id: step.step_id.clone(),
+                        kind: step.kind.clone(),
+                        config: step.config.clone(),
+                    };
+                    state.leased.insert(
+                        key,
+                        Lease {
+                            step,
+                            token,
+                            expires_at: Instant::now() + self.visibility_timeout,
+                        },
+                    );
+                    return Some(payload);
+                }
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return None;
+            }
+            let _ = tokio::time::timeout(remaining, self.notify.notified()).await;
+        }
+    }
+
+    /// Reports a task as succeeded. Rejected with `EngineError::StaleLease`
+    /// if `lease_token` doesn't match the current lease (it expired and was
+    /// re-queued, or this is a duplicate report).
+    pub async fn complete(&self, run_id: Uuid, step_id: &str, lease_token: u64, output: Value) -> Result<(), EngineError> {
+        self.resolve(run_id, step_id, lease_token, Ok(output)).await
+    }
+
+    /// Reports a task as failed. Same lease-token guard as `complete`.
+    pub async fn fail(&self, run_id: Uuid, step_id: &str, lease_token: u64, error: String) -> Result<(), EngineError> {
+        self.resolve(run_id, step_id, lease_token, Err(error)).await
+    }
+
+    async fn resolve(&self, run_id: Uuid, step_id: &str, lease_token: u64, result: Result<Value, String>) -> Result<(), EngineError> {
+        let key = (run_id, step_id.to_string());
+        let mut state = self.state.lock().await;
+
+        match state.leased.get(&key) {
+            Some(lease) if lease.token == lease_token => {}
+            _ => return Err(EngineError::StaleLease),
+        }
+        state.leased.remove(&key);
+        if let Some(tx) = state.waiters.remove(&key) {
+            let _ = tx.send(result);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn ready_step() -> ReadyStep {
+        ReadyStep {
+            run_id: Uuid::new_v4(),
+            step_id: "a".to_string(),
+            kind: "http".to_string(),
+            config: Value::Null,
+        }
+    }
+
+    #[tokio::test]
+    async fn acquire_then_complete_resolves_submit() {
+        let queue = Arc::new(TaskQueue::new(Duration::from_secs(30)));
+        let step = ready_step();
+        let (run_id, step_id) = (step.run_id, step.step_id.clone());
+
+        let submit = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.submit(step).await }
+        });
+
+        let payload = queue.acquire(Duration::from_millis(50)).await.unwrap();
+        queue
+            .complete(run_id, &step_id, payload.lease_token, Value::from(42))
+            .await
+            .unwrap();
+
+        let output = submit.await.unwrap().unwrap();
+        assert_eq!(output, Value::from(42));
+    }
+
+    #[tokio::test]
+    async fn stale_lease_token_is_rejected() {
+        let queue = TaskQueue::new(Duration::from_secs(30));
+        let step = ready_step();
+        let (run_id, step_id) = (step.run_id, step.step_id.clone());
+        queue.state.lock().await.pending.push_back(step);
+
+        let payload = queue.acquire(Duration::from_millis(50)).await.unwrap();
+        let wrong_token = payload.lease_token + 1;
+
+        let result = queue.complete(run_id, &step_id, wrong_token, Value::Null).await;
+        assert!(matches!(result, Err(EngineError::StaleLease)));
+    }
+
+    #[tokio::test]
+    async fn double_complete_is_rejected() {
+        let queue = TaskQueue::new(Duration::from_secs(30));
+        let step = ready_step();
+        let (run_id, step_id) = (step.run_id, step.step_id.clone());
+        queue.state.lock().await.pending.push_back(step);
+
+        let payload = queue.acquire(Duration::from_millis(50)).await.unwrap();
+        queue.complete(run_id, &step_id, payload.lease_token, Value::Null).await.unwrap();
+
+        let second = queue.complete(run_id, &step_id, payload.lease_token, Value::Null).await;
+        assert!(matches!(second, Err(EngineError::StaleLease)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn expired_lease_is_reclaimed_for_another_acquire() {
+        let queue = TaskQueue::new(Duration::from_millis(10));
+        let step = ready_step();
+        queue.state.lock().await.pending.push_back(step);
+
+        let first = queue.acquire(Duration::from_millis(5)).await;
+        assert!(first.is_some());
+
+        tokio::time::advance(Duration::from_millis(20)).await;
+
+        let second = queue.acquire(Duration::from_millis(5)).await;
+        assert!(second.is_some(), "expired lease should be requeued and reacquired");
+    }
+}